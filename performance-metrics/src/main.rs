@@ -8,9 +8,10 @@ mod performance_tests;
 use performance_tests::*;
 use serde_derive::{Deserialize, Serialize};
 use std::{
-    collections::HashSet,
-    env, fmt,
+    collections::{HashMap, HashSet},
+    env, fmt, fs,
     hash::{Hash, Hasher},
+    io::Write,
     sync::mpsc::channel,
     thread,
     time::Duration,
@@ -20,6 +21,8 @@ use std::{
 enum Error {
     TestTimeout,
     TestFailed,
+    BaselineFileMissing,
+    BaselineFileMalformed,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -29,6 +32,23 @@ pub struct PerformanceTestResult {
     std_dev: f64,
     max: f64,
     min: f64,
+    p50: f64,
+    p90: f64,
+    p99: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    baseline_mean: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    delta_percent: Option<f64>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    regressed: bool,
+    // Set when the test was run as one step of a load sweep: the offered
+    // fio/packet rate driving this particular sample.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offered_load: Option<u64>,
+}
+
+fn is_false(b: &bool) -> bool {
+    !b
 }
 
 #[derive(Deserialize, Serialize)]
@@ -36,9 +56,88 @@ pub struct MetricsReport {
     pub git_human_readable: String,
     pub git_revision: String,
     pub date: String,
+    pub system_info: SystemInfo,
     pub results: Vec<PerformanceTestResult>,
 }
 
+// Describes the machine a report was captured on, so numbers from
+// different hosts aren't compared as if they were apples to apples.
+#[derive(Deserialize, Serialize)]
+pub struct SystemInfo {
+    pub cpu_model: String,
+    pub physical_cores: u32,
+    pub logical_cores: u32,
+    pub total_memory_kb: u64,
+    pub kvm_available: bool,
+    pub kernel_version: String,
+    pub build_features: Vec<String>,
+    // A fixed short CPU micro-benchmark run once at start-up, so results
+    // from different hosts can be normalized against a known baseline
+    // machine. Only populated when `REFERENCE_SCORE` is set, since it
+    // adds a fixed amount of time to every run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference_score: Option<f64>,
+}
+
+fn shell(cmd: &str) -> String {
+    let output = test_infra::exec_host_command_output(cmd);
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn gather_system_info() -> SystemInfo {
+    let logical_cores = shell("nproc").parse().unwrap_or(0);
+    let physical_cores = shell(
+        "lscpu | awk -F: '/^Core\\(s\\) per socket/{c=$2} /^Socket\\(s\\)/{s=$2} END{print c*s}'",
+    )
+    .parse()
+    .unwrap_or(logical_cores);
+
+    SystemInfo {
+        cpu_model: shell("grep -m1 'model name' /proc/cpuinfo | cut -d: -f2 | xargs"),
+        physical_cores,
+        logical_cores,
+        total_memory_kb: shell("grep MemTotal /proc/meminfo | awk '{print $2}'")
+            .parse()
+            .unwrap_or(0),
+        kvm_available: std::path::Path::new("/dev/kvm").exists(),
+        kernel_version: shell("uname -r"),
+        build_features: build_features(),
+        reference_score: env::var("REFERENCE_SCORE").ok().map(|_| reference_score()),
+    }
+}
+
+// The cloud-hypervisor hypervisor backends this binary was built with.
+fn build_features() -> Vec<String> {
+    let mut features = Vec::new();
+    if cfg!(feature = "kvm") {
+        features.push("kvm".to_string());
+    }
+    if cfg!(feature = "mshv") {
+        features.push("mshv".to_string());
+    }
+    if cfg!(feature = "tdx") {
+        features.push("tdx".to_string());
+    }
+    if cfg!(feature = "sev_snp") {
+        features.push("sev_snp".to_string());
+    }
+    features
+}
+
+// A short, fixed CPU micro-benchmark: lower is faster. Not meant to be a
+// precise benchmark, just a cheap way to normalize results captured on
+// different hosts against each other.
+fn reference_score() -> f64 {
+    let start = std::time::Instant::now();
+    let mut acc: u64 = 0;
+    for i in 0..50_000_000u64 {
+        acc = acc.wrapping_add(i.wrapping_mul(2_654_435_761));
+    }
+    std::hint::black_box(acc);
+    start.elapsed().as_secs_f64()
+}
+
+#[derive(Clone)]
 pub struct PerformanceTestControl {
     test_time: u32,
     test_iterations: u32,
@@ -46,6 +145,26 @@ pub struct PerformanceTestControl {
     queue_size: Option<u32>,
     net_rx: Option<bool>,
     fio_ops: Option<FioOps>,
+    // Bounds a single invocation of `func_ptr`. Unlike `test_time`/
+    // `test_iterations`, which size the whole test, exceeding this is
+    // treated as a fatal error for the test rather than just one slow
+    // sample.
+    request_timeout: Option<Duration>,
+    // Load-sweep parameters: when all three of `rate_start`/`rate_step`/
+    // `rate_max` are set, `PerformanceTest::run` ramps the offered load
+    // (fio rate / packet rate) from `rate_start` to `rate_max` in
+    // `rate_step` increments instead of running a single fixed-rate test,
+    // producing one `PerformanceTestResult` per step so callers can chart
+    // a throughput-vs-offered-load curve.
+    rate_start: Option<u64>,
+    rate_step: Option<u64>,
+    rate_max: Option<u64>,
+    // Number of iterations to run at the `rate_max` ceiling, in addition
+    // to `test_iterations` at the lower steps.
+    max_iter: Option<u32>,
+    // The offered load for the current run, set by the sweep driver
+    // before invoking `func_ptr`; left unset for a fixed-rate test.
+    offered_load: Option<u64>,
 }
 
 impl fmt::Display for PerformanceTestControl {
@@ -66,6 +185,9 @@ impl fmt::Display for PerformanceTestControl {
         if let Some(o) = &self.fio_ops {
             output = format!("{}, fio_ops = {}", output, o);
         }
+        if let Some(o) = self.offered_load {
+            output = format!("{}, offered_load = {}", output, o);
+        }
 
         write!(f, "{}", output)
     }
@@ -80,10 +202,38 @@ impl Default for PerformanceTestControl {
             queue_size: Default::default(),
             net_rx: Default::default(),
             fio_ops: Default::default(),
+            request_timeout: env::var("REQUEST_TIMEOUT")
+                .ok()
+                .and_then(|o| parse_duration(&o)),
+            // Sweep mode is opt-in: every test in `TEST_LIST` picks up the
+            // same `SWEEP_RATE_*` knobs via `Default::default()`, so setting
+            // them turns every test into a sweep without touching
+            // `TEST_LIST`. That includes the boot-time and latency tests,
+            // whose workloads do not use `offered_load` and so repeat the
+            // same measurement at each step.
+            rate_start: env::var("SWEEP_RATE_START").ok().and_then(|o| o.parse().ok()),
+            rate_step: env::var("SWEEP_RATE_STEP").ok().and_then(|o| o.parse().ok()),
+            rate_max: env::var("SWEEP_RATE_MAX").ok().and_then(|o| o.parse().ok()),
+            max_iter: env::var("SWEEP_MAX_ITER").ok().and_then(|o| o.parse().ok()),
+            offered_load: Default::default(),
         }
     }
 }
 
+// Parse a duration of the form "30s", "500ms", or "2m". Returns `None` on
+// anything else so a malformed `REQUEST_TIMEOUT` is silently ignored
+// rather than panicking test harness start-up.
+fn parse_duration(s: &str) -> Option<Duration> {
+    let (value, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit())?);
+    let value: u64 = value.parse().ok()?;
+    match unit {
+        "ms" => Some(Duration::from_millis(value)),
+        "s" => Some(Duration::from_secs(value)),
+        "m" => Some(Duration::from_secs(value * 60)),
+        _ => None,
+    }
+}
+
 /// A performance test should finish within the a certain time-out and
 /// return a performance metrics number (including the average number and
 /// standard deviation)
@@ -108,30 +258,148 @@ impl PartialEq for PerformanceTest {
 impl Eq for PerformanceTest {}
 
 impl PerformanceTest {
-    pub fn run(&self) -> PerformanceTestResult {
+    // Returns the sweep's (rate_start, rate_step, rate_max) when a valid
+    // load sweep is configured. A `rate_step` of zero would never reach
+    // `rate_max`, so a misconfigured one is treated the same as "no sweep
+    // configured" rather than spinning forever.
+    fn sweep_config(&self) -> Option<(u64, u64, u64)> {
+        match (
+            self.control.rate_start,
+            self.control.rate_step,
+            self.control.rate_max,
+        ) {
+            (Some(rate_start), Some(rate_step), Some(rate_max)) if rate_step > 0 => {
+                Some((rate_start, rate_step, rate_max))
+            }
+            (Some(_), Some(0), Some(_)) => {
+                eprintln!(
+                    "[Error] Test '{}' has rate_step = 0; ignoring the load-sweep configuration",
+                    self.name
+                );
+                None
+            }
+            _ => None,
+        }
+    }
+
+    // The sequence of offered-load values a sweep runs at, ending exactly
+    // on `rate_max` even when it isn't an even multiple of `rate_step`
+    // away from `rate_start` (e.g. 0, 3, 6, 9, 10 for step 3 up to 10).
+    fn sweep_rates(rate_start: u64, rate_step: u64, rate_max: u64) -> Vec<u64> {
+        let mut rates = Vec::new();
+        let mut rate = rate_start;
+        loop {
+            rates.push(rate);
+            if rate >= rate_max {
+                break;
+            }
+            rate = (rate + rate_step).min(rate_max);
+        }
+        rates
+    }
+
+    // Runs the test, returning one result, or one result per step when a
+    // load sweep is configured.
+    pub fn run(&self) -> Result<Vec<PerformanceTestResult>, Error> {
+        let Some((rate_start, rate_step, rate_max)) = self.sweep_config() else {
+            return Ok(vec![self.run_step(&self.control)?]);
+        };
+
+        let mut results = Vec::new();
+        for rate in Self::sweep_rates(rate_start, rate_step, rate_max) {
+            let mut step_control = self.control.clone();
+            step_control.offered_load = Some(rate);
+            if rate >= rate_max {
+                if let Some(max_iter) = self.control.max_iter {
+                    step_control.test_iterations = max_iter;
+                }
+            }
+
+            results.push(self.run_step(&step_control)?);
+        }
+
+        Ok(results)
+    }
+
+    // Run `test_iterations` (or `max_iter`, for the ceiling step of a
+    // sweep) invocations of `func_ptr` under `control` and reduce them to
+    // a single `PerformanceTestResult`.
+    fn run_step(&self, control: &PerformanceTestControl) -> Result<PerformanceTestResult, Error> {
         let mut metrics = Vec::new();
-        for _ in 0..self.control.test_iterations {
-            metrics.push((self.func_ptr)(&self.control));
+        for _ in 0..control.test_iterations {
+            metrics.push(self.run_iteration(control)?);
         }
 
         let mean = mean(&metrics).unwrap();
         let std_dev = std_deviation(&metrics).unwrap();
         let max = metrics.clone().into_iter().reduce(f64::max).unwrap();
         let min = metrics.clone().into_iter().reduce(f64::min).unwrap();
+        let p50 = percentile(&metrics, 50.0);
+        let p90 = percentile(&metrics, 90.0);
+        let p99 = percentile(&metrics, 99.0);
 
-        PerformanceTestResult {
+        Ok(PerformanceTestResult {
             name: self.name.to_string(),
             mean,
             std_dev,
             max,
             min,
-        }
+            p50,
+            p90,
+            p99,
+            baseline_mean: None,
+            delta_percent: None,
+            regressed: false,
+            offered_load: control.offered_load,
+        })
+    }
+
+    // Run a single iteration, bounding it by `request_timeout` when set.
+    // Unlike the whole-test timeout in `run_test_with_timetout`, exceeding
+    // this is fatal: the remaining iterations are abandoned and any
+    // processes the iteration spawned are torn down before returning.
+    fn run_iteration(&self, control: &PerformanceTestControl) -> Result<f64, Error> {
+        let Some(timeout) = control.request_timeout else {
+            return Ok((self.func_ptr)(control));
+        };
+
+        let control = control.clone();
+        let func_ptr = self.func_ptr;
+        let (sender, receiver) = channel();
+        thread::spawn(move || {
+            let _ = sender.send(func_ptr(&control));
+        });
+
+        receiver.recv_timeout(timeout).map_err(|_| {
+            eprintln!(
+                "[Error] Test '{}' iteration exceeded the per-iteration timeout of {:?}; aborting remaining iterations",
+                self.name, timeout
+            );
+            cleanup_tests();
+            Error::TestTimeout
+        })
     }
 
     // Calculate the timeout for each test
     // Note: To cover the setup/cleanup time, 20s is added for each iteration of the test
     pub fn calc_timeout(&self) -> u64 {
-        ((self.control.test_time + 20) * self.control.test_iterations) as u64
+        ((self.control.test_time + 20) * self.total_iterations()) as u64
+    }
+
+    // Total number of `func_ptr` invocations this test will perform,
+    // across every step of a load sweep when one is configured.
+    fn total_iterations(&self) -> u32 {
+        let Some((rate_start, rate_step, rate_max)) = self.sweep_config() else {
+            return self.control.test_iterations;
+        };
+
+        // The ceiling step runs `max_iter` iterations in place of
+        // `test_iterations` when `max_iter` is set.
+        let steps = Self::sweep_rates(rate_start, rate_step, rate_max).len() as u32;
+        match self.control.max_iter {
+            Some(max_iter) => (steps - 1) * self.control.test_iterations + max_iter,
+            None => steps * self.control.test_iterations,
+        }
     }
 }
 
@@ -145,6 +413,23 @@ fn mean(data: &[f64]) -> Option<f64> {
     }
 }
 
+// Linear-interpolated percentile (e.g. `p == 90.0` for p90), since tail
+// latency matters far more than the mean for the latency tests.
+fn percentile(data: &[f64], p: f64) -> f64 {
+    let mut sorted = data.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let rank = (p / 100.0) * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    if lower == upper {
+        sorted[lower]
+    } else {
+        let weight = rank - lower as f64;
+        sorted[lower] * (1.0 - weight) + sorted[upper] * weight
+    }
+}
+
 fn std_deviation(data: &[f64]) -> Option<f64> {
     let count = data.len();
 
@@ -315,26 +600,30 @@ lazy_static! {
     };
 }
 
-fn run_test_with_timetout(test: &'static PerformanceTest) -> Result<PerformanceTestResult, Error> {
-    let (sender, receiver) = channel::<Result<PerformanceTestResult, Error>>();
+fn run_test_with_timetout(
+    test: &'static PerformanceTest,
+) -> Result<Vec<PerformanceTestResult>, Error> {
+    let (sender, receiver) = channel::<Result<Vec<PerformanceTestResult>, Error>>();
     thread::spawn(move || {
         println!("Test '{}' running .. ({})", test.name, test.control);
 
         let output = match std::panic::catch_unwind(|| test.run()) {
-            Ok(test_result) => {
-                println!(
-                    "Test '{}' .. ok: mean = {}, std_dev = {}",
-                    test_result.name, test_result.mean, test_result.std_dev
-                );
-                Ok(test_result)
+            Ok(Ok(test_results)) => {
+                for test_result in &test_results {
+                    println!(
+                        "Test '{}' .. ok: mean = {}, std_dev = {}",
+                        test_result.name, test_result.mean, test_result.std_dev
+                    );
+                }
+                Ok(test_results)
             }
+            Ok(Err(e)) => Err(e),
             Err(_) => Err(Error::TestFailed),
         };
 
         let _ = sender.send(output);
     });
 
-    // Todo: Need to cleanup/kill all hanging child processes
     let test_timeout = test.calc_timeout();
     receiver
         .recv_timeout(Duration::from_secs(test_timeout))
@@ -343,15 +632,200 @@ fn run_test_with_timetout(test: &'static PerformanceTest) -> Result<PerformanceT
                 "[Error] Test '{}' time-out after {} seconds",
                 test.name, test_timeout
             );
+            cleanup_tests();
             Error::TestTimeout
         })?
 }
 
+// Default number of baseline standard deviations a new mean may drift by
+// before it is considered a regression.
+const DEFAULT_REGRESSION_SIGMA: f64 = 3.0;
+
+// Keyed by `(name, offered_load)` rather than `name` alone: a load-sweep
+// test produces multiple results sharing one name, one per sweep step, so
+// keying by name only would collapse them down to whichever step loaded
+// last and compare every step against that single arbitrary baseline.
+fn load_baseline(path: &str) -> Result<HashMap<(String, Option<u64>), PerformanceTestResult>, Error> {
+    let content = fs::read_to_string(path).map_err(|_| Error::BaselineFileMissing)?;
+    let report: MetricsReport =
+        serde_json::from_str(&content).map_err(|_| Error::BaselineFileMalformed)?;
+
+    Ok(report
+        .results
+        .into_iter()
+        .map(|r| ((r.name.clone(), r.offered_load), r))
+        .collect())
+}
+
+// Flag `result` as regressed if its mean has drifted from the matching
+// baseline result by more than `regression_sigma` baseline standard
+// deviations, or by more than `regression_percent` percent (whichever is
+// configured). Returns whether a regression was detected.
+fn detect_regression(
+    result: &mut PerformanceTestResult,
+    baseline: &PerformanceTestResult,
+    regression_sigma: f64,
+    regression_percent: Option<f64>,
+) -> bool {
+    let delta_percent = if baseline.mean != 0.0 {
+        (result.mean - baseline.mean) / baseline.mean * 100.0
+    } else {
+        0.0
+    };
+
+    let sigma_exceeded = baseline.std_dev > 0.0
+        && (result.mean - baseline.mean).abs() > regression_sigma * baseline.std_dev;
+    let percent_exceeded =
+        regression_percent.is_some_and(|p| delta_percent.abs() > p);
+
+    result.baseline_mean = Some(baseline.mean);
+    result.delta_percent = Some(delta_percent);
+    result.regressed = sigma_exceeded || percent_exceeded;
+
+    result.regressed
+}
+
 fn date() -> String {
     let output = test_infra::exec_host_command_output("date");
     String::from_utf8_lossy(&output.stdout).trim().to_string()
 }
 
+// Escape a Prometheus label value per the text exposition format.
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+// Push every `PerformanceTestResult` to a Prometheus push gateway as one
+// gauge per metric, labeled with the test name and git provenance, so
+// long-running perf jobs can feed a time-series database.
+fn push_to_prometheus(metrics_report: &MetricsReport, host: &str, job: &str) {
+    let mut body = String::new();
+    for metric in ["mean", "std_dev", "min", "max"] {
+        body.push_str(&format!("# TYPE performance_metrics_{metric} gauge\n"));
+        for result in &metrics_report.results {
+            let labels = format!(
+                "name=\"{}\",git_revision=\"{}\",git_human_readable=\"{}\"",
+                escape_label_value(&result.name),
+                escape_label_value(&metrics_report.git_revision),
+                escape_label_value(&metrics_report.git_human_readable),
+            );
+            let value = match metric {
+                "mean" => result.mean,
+                "std_dev" => result.std_dev,
+                "min" => result.min,
+                _ => result.max,
+            };
+            body.push_str(&format!(
+                "performance_metrics_{metric}{{{labels}}} {value}\n"
+            ));
+        }
+    }
+
+    // Shelled out to `curl` with `host`/`job` interpolated into the command
+    // string, a `'` in either would break out of the quoting and let
+    // arbitrary shell commands ride along with the metrics push. Pass the
+    // URL as a single argv element instead so there is no shell to escape.
+    let url = format!("http://{host}/metrics/job/{job}");
+    let mut child = match std::process::Command::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--data-binary",
+            "@-",
+            &url,
+        ])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            eprintln!("[Error] Failed to launch curl to push metrics to '{url}': {e}");
+            return;
+        }
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Err(e) = stdin.write_all(body.as_bytes()) {
+            eprintln!("[Error] Failed to write metrics to curl's stdin for '{url}': {e}");
+        }
+    }
+
+    match child.wait_with_output() {
+        Ok(output) if !output.status.success() => {
+            eprintln!(
+                "[Error] Failed to push metrics to Prometheus push gateway at '{}': {}",
+                url,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        Err(e) => {
+            eprintln!("[Error] Failed to push metrics to Prometheus push gateway at '{url}': {e}");
+        }
+        _ => {}
+    }
+}
+
+// Separates result emission from the test engine, mirroring how libtest
+// keeps its formatters (pretty/terse/json) independent of the runner.
+trait Formatter {
+    fn format(&self, report: &MetricsReport) -> String;
+}
+
+struct JsonFormatter;
+
+impl Formatter for JsonFormatter {
+    fn format(&self, report: &MetricsReport) -> String {
+        serde_json::to_string_pretty(report).unwrap()
+    }
+}
+
+// A human-readable table, one row per test.
+struct PrettyFormatter;
+
+impl Formatter for PrettyFormatter {
+    fn format(&self, report: &MetricsReport) -> String {
+        let mut output = format!(
+            "{} ({})\n",
+            report.git_human_readable, report.system_info.cpu_model
+        );
+        for r in &report.results {
+            output.push_str(&format!(
+                "  {:<55} mean = {:>12.3} std_dev = {:>10.3} p50 = {:>10.3} p90 = {:>10.3} p99 = {:>10.3}\n",
+                r.name, r.mean, r.std_dev, r.p50, r.p90, r.p99
+            ));
+        }
+        output
+    }
+}
+
+// One `name=mean` pair per line, for quick scripted consumption.
+struct TerseFormatter;
+
+impl Formatter for TerseFormatter {
+    fn format(&self, report: &MetricsReport) -> String {
+        report
+            .results
+            .iter()
+            .map(|r| format!("{}={}", r.name, r.mean))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn formatter_for(format: &str) -> Box<dyn Formatter> {
+    match format {
+        "pretty" => Box::new(PrettyFormatter),
+        "terse" => Box::new(TerseFormatter),
+        _ => Box::new(JsonFormatter),
+    }
+}
+
 fn main() {
     let test_filter = env::var("TEST_FILTER").map_or("".to_string(), |o| o);
 
@@ -360,6 +834,7 @@ fn main() {
         git_human_readable: env!("GIT_HUMAN_READABLE").to_string(),
         git_revision: env!("GIT_REVISION").to_string(),
         date: date(),
+        system_info: gather_system_info(),
         results: Vec::new(),
     };
 
@@ -369,7 +844,7 @@ fn main() {
         if test.name.contains(&test_filter) {
             match run_test_with_timetout(test) {
                 Ok(r) => {
-                    metrics_report.results.push(r);
+                    metrics_report.results.extend(r);
                 }
                 Err(e) => {
                     eprintln!("Aborting test due to error: '{:?}'", e);
@@ -381,9 +856,234 @@ fn main() {
 
     cleanup_tests();
 
-    // Todo: Report/upload to the metrics database
-    println!(
-        "\n\nTests result in json format: \n {}",
-        serde_json::to_string_pretty(&metrics_report).unwrap()
-    );
+    let mut regression_detected = false;
+    if let Ok(baseline_path) = env::var("BASELINE") {
+        let regression_sigma = env::var("REGRESSION_SIGMA")
+            .ok()
+            .and_then(|o| o.parse::<f64>().ok())
+            .unwrap_or(DEFAULT_REGRESSION_SIGMA);
+        let regression_percent = env::var("REGRESSION_PERCENT")
+            .ok()
+            .and_then(|o| o.parse::<f64>().ok());
+
+        match load_baseline(&baseline_path) {
+            Ok(baseline_results) => {
+                for result in metrics_report.results.iter_mut() {
+                    let key = (result.name.clone(), result.offered_load);
+                    if let Some(baseline) = baseline_results.get(&key) {
+                        if detect_regression(result, baseline, regression_sigma, regression_percent)
+                        {
+                            eprintln!(
+                                "[Error] Test '{}' regressed: mean {} vs baseline {} ({:+.2}%)",
+                                result.name,
+                                result.mean,
+                                baseline.mean,
+                                result.delta_percent.unwrap()
+                            );
+                            regression_detected = true;
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "[Error] Unable to load baseline report '{}': {:?}",
+                    baseline_path, e
+                );
+                regression_detected = true;
+            }
+        }
+    }
+
+    if let (Ok(host), Ok(job)) = (env::var("PROMETHEUS_HOST"), env::var("PROMETHEUS_JOB")) {
+        push_to_prometheus(&metrics_report, &host, &job);
+    }
+
+    let format = env::var("FORMAT").unwrap_or_else(|_| "json".to_string());
+    let formatter = formatter_for(&format);
+    if format == "json" {
+        println!(
+            "\n\nTests result in json format: \n {}",
+            formatter.format(&metrics_report)
+        );
+    } else {
+        println!("\n\n{}", formatter.format(&metrics_report));
+    }
+
+    if regression_detected {
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result_with(name: &str, mean: f64, std_dev: f64) -> PerformanceTestResult {
+        PerformanceTestResult {
+            name: name.to_string(),
+            mean,
+            std_dev,
+            max: mean,
+            min: mean,
+            p50: mean,
+            p90: mean,
+            p99: mean,
+            baseline_mean: None,
+            delta_percent: None,
+            regressed: false,
+            offered_load: None,
+        }
+    }
+
+    #[test]
+    fn detect_regression_flags_sigma_exceeded() {
+        let mut result = result_with("t", 120.0, 1.0);
+        let baseline = result_with("t", 100.0, 1.0);
+
+        assert!(detect_regression(&mut result, &baseline, 3.0, None));
+        assert_eq!(result.baseline_mean, Some(100.0));
+        assert_eq!(result.delta_percent, Some(20.0));
+    }
+
+    #[test]
+    fn detect_regression_ignores_small_drift() {
+        let mut result = result_with("t", 100.5, 1.0);
+        let baseline = result_with("t", 100.0, 1.0);
+
+        assert!(!detect_regression(&mut result, &baseline, 3.0, None));
+    }
+
+    #[test]
+    fn detect_regression_with_zero_baseline_std_dev_falls_back_to_percent() {
+        let mut result = result_with("t", 105.0, 1.0);
+        let baseline = result_with("t", 100.0, 0.0);
+
+        // No baseline variance to compare against, and no percent
+        // threshold configured: nothing to flag.
+        assert!(!detect_regression(&mut result, &baseline, 3.0, None));
+        // With a percent threshold, the 5% drift is still caught.
+        assert!(detect_regression(&mut result, &baseline, 3.0, Some(1.0)));
+    }
+
+    #[test]
+    fn percentile_single_sample() {
+        assert_eq!(percentile(&[42.0], 99.0), 42.0);
+    }
+
+    #[test]
+    fn percentile_interpolates_between_samples() {
+        let data = vec![1.0, 2.0, 3.0, 4.0];
+        assert_eq!(percentile(&data, 50.0), 2.5);
+        assert_eq!(percentile(&data, 0.0), 1.0);
+        assert_eq!(percentile(&data, 100.0), 4.0);
+    }
+
+    #[test]
+    fn parse_duration_units() {
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_duration("2m"), Some(Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn parse_duration_rejects_malformed_input() {
+        assert_eq!(parse_duration("abc"), None);
+        assert_eq!(parse_duration("10"), None);
+        assert_eq!(parse_duration("10x"), None);
+        assert_eq!(parse_duration(""), None);
+    }
+
+    fn dummy_test() -> PerformanceTest {
+        PerformanceTest {
+            name: "dummy",
+            func_ptr: |_| 0.0,
+            control: PerformanceTestControl::default(),
+        }
+    }
+
+    #[test]
+    fn sweep_rates_handles_uneven_step() {
+        assert_eq!(PerformanceTest::sweep_rates(0, 3, 10), vec![0, 3, 6, 9, 10]);
+    }
+
+    #[test]
+    fn sweep_rates_single_step_when_start_equals_max() {
+        assert_eq!(PerformanceTest::sweep_rates(5, 1, 5), vec![5]);
+    }
+
+    #[test]
+    fn sweep_config_treats_zero_step_as_no_sweep() {
+        let mut test = dummy_test();
+        test.control.rate_start = Some(0);
+        test.control.rate_step = Some(0);
+        test.control.rate_max = Some(10);
+
+        assert!(test.sweep_config().is_none());
+    }
+
+    #[test]
+    fn sweep_config_returns_values_when_valid() {
+        let mut test = dummy_test();
+        test.control.rate_start = Some(0);
+        test.control.rate_step = Some(2);
+        test.control.rate_max = Some(8);
+
+        assert_eq!(test.sweep_config(), Some((0, 2, 8)));
+    }
+
+    #[test]
+    fn sweep_config_is_none_when_unconfigured() {
+        assert!(dummy_test().sweep_config().is_none());
+    }
+
+    fn sample_report() -> MetricsReport {
+        MetricsReport {
+            git_human_readable: "test".to_string(),
+            git_revision: "deadbeef".to_string(),
+            date: "today".to_string(),
+            system_info: SystemInfo {
+                cpu_model: "test-cpu".to_string(),
+                physical_cores: 1,
+                logical_cores: 2,
+                total_memory_kb: 1024,
+                kvm_available: false,
+                kernel_version: "0.0.0".to_string(),
+                build_features: vec![],
+                reference_score: None,
+            },
+            results: vec![result_with("perf_test", 10.0, 0.0)],
+        }
+    }
+
+    #[test]
+    fn json_formatter_round_trips() {
+        let report = sample_report();
+        let json = JsonFormatter.format(&report);
+        let parsed: MetricsReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.results[0].name, "perf_test");
+        assert_eq!(parsed.results[0].mean, 10.0);
+    }
+
+    #[test]
+    fn terse_formatter_emits_name_equals_mean() {
+        let report = sample_report();
+        assert_eq!(TerseFormatter.format(&report), "perf_test=10");
+    }
+
+    #[test]
+    fn pretty_formatter_includes_percentiles() {
+        let report = sample_report();
+        let output = PrettyFormatter.format(&report);
+        assert!(output.contains("perf_test"));
+        assert!(output.contains("p99"));
+    }
+
+    #[test]
+    fn formatter_for_defaults_to_json_for_unknown_names() {
+        let report = sample_report();
+        assert!(formatter_for("unknown").format(&report).contains("\"name\""));
+        assert!(formatter_for("terse").format(&report).contains("perf_test=10"));
+        assert!(formatter_for("pretty").format(&report).contains("mean ="));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,190 @@
+// Workload implementations backing the entries in `TEST_LIST` (main.rs).
+// Each function here is one `PerformanceTest::func_ptr` and returns one
+// performance sample (boot time in ms, throughput in bits per second,
+// latency in us, ...).
+//
+// NOTE: this source tree does not include cloud-hypervisor's actual
+// guest-provisioning harness (the code that boots a guest under the VMM
+// and runs a workload inside it), so these are local-host stand-ins that
+// exercise the same command-line tools (fio, iperf3) directly against
+// the host rather than through a booted guest. They are good enough to
+// exercise the harness's control flow (timeouts, sweeps, formatting) but
+// do not measure real virtio-net/virtio-block performance. Replacing
+// them with the real guest-driven workloads is tracked separately.
+
+use crate::PerformanceTestControl;
+use std::fmt;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+// PIDs of processes currently spawned by `run_tracked`, so a stuck
+// invocation can be killed by `cleanup_tests()` on a per-iteration or
+// whole-test timeout instead of being silently abandoned.
+static CHILD_PIDS: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+// Run `cmd` under a shell, tracking its PID for the duration of the call.
+fn run_tracked(cmd: &str) -> std::process::Output {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(cmd)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to spawn command");
+
+    let pid = child.id();
+    CHILD_PIDS.lock().unwrap().push(pid);
+
+    let output = child.wait_with_output().expect("failed to wait on command");
+
+    CHILD_PIDS.lock().unwrap().retain(|&p| p != pid);
+    output
+}
+
+#[derive(Clone, Copy)]
+pub enum FioOps {
+    Read,
+    Write,
+    RandomRead,
+    RandomWrite,
+}
+
+impl fmt::Display for FioOps {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rw = match self {
+            FioOps::Read => "read",
+            FioOps::Write => "write",
+            FioOps::RandomRead => "randread",
+            FioOps::RandomWrite => "randwrite",
+        };
+        write!(f, "{}", rw)
+    }
+}
+
+pub fn init_tests() {
+    CHILD_PIDS.lock().unwrap().clear();
+}
+
+// Kill every process still tracked by `run_tracked`. Called on a
+// per-iteration or whole-test timeout so a stuck boot/fio/iperf
+// invocation doesn't keep running after the harness has moved on.
+pub fn cleanup_tests() {
+    let pids: Vec<u32> = CHILD_PIDS.lock().unwrap().drain(..).collect();
+    for pid in pids {
+        let _ = test_infra::exec_host_command_output(&format!("kill -9 {pid}"));
+    }
+}
+
+pub fn performance_boot_time(control: &PerformanceTestControl) -> f64 {
+    boot_time_ms(control)
+}
+
+pub fn performance_boot_time_pmem(control: &PerformanceTestControl) -> f64 {
+    boot_time_ms(control)
+}
+
+fn boot_time_ms(_control: &PerformanceTestControl) -> f64 {
+    let start = std::time::Instant::now();
+    run_tracked("cloud-hypervisor --version");
+    start.elapsed().as_secs_f64() * 1000.0
+}
+
+pub fn performance_net_latency(_control: &PerformanceTestControl) -> f64 {
+    let output = run_tracked("ping -c 1 -q 127.0.0.1");
+    parse_ping_latency_us(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_ping_latency_us(output: &str) -> f64 {
+    // "rtt min/avg/max/mdev = 0.010/0.012/0.015/0.002 ms"
+    output
+        .lines()
+        .find(|l| l.contains("min/avg/max"))
+        .and_then(|l| l.split('=').nth(1))
+        .and_then(|l| l.split('/').nth(1))
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .map(|ms| ms * 1000.0)
+        .unwrap_or(0.0)
+}
+
+// Drive virtio-net throughput with iperf3 across `control.queue_num`
+// queue pairs, in the direction selected by `control.net_rx`. When a load
+// sweep is in progress, `control.offered_load` caps the offered bandwidth
+// (bits/sec) so each sweep step actually drives a different packet rate
+// instead of re-running the same unlimited-rate test.
+pub fn performance_net_throughput(control: &PerformanceTestControl) -> f64 {
+    let direction = if control.net_rx.unwrap_or(true) {
+        "-R"
+    } else {
+        ""
+    };
+    let parallel = control.queue_num.unwrap_or(1);
+    let bandwidth = match control.offered_load {
+        Some(rate) => format!("-b {rate}"),
+        None => String::new(),
+    };
+
+    let cmd = format!(
+        "iperf3 -c 127.0.0.1 -t {} -P {} {} {}",
+        control.test_time, parallel, direction, bandwidth
+    );
+    let output = run_tracked(&cmd);
+    parse_iperf_bps(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_iperf_bps(output: &str) -> f64 {
+    // "[SUM]   0.00-1.00   sec  1.10 GBytes  9.48 Gbits/sec"
+    // The value is the field just before the "*bits/sec" unit, whose
+    // position differs between "[SUM]" and "[  5]" lines.
+    output
+        .lines()
+        .filter(|l| l.contains("sender") || l.contains("SUM"))
+        .last()
+        .and_then(|l| {
+            let fields: Vec<&str> = l.split_whitespace().collect();
+            let unit = fields.iter().position(|f| f.ends_with("bits/sec"))?;
+            let value = fields.get(unit.checked_sub(1)?)?.parse::<f64>().ok()?;
+            let scale = match fields[unit] {
+                "Gbits/sec" => 1_000_000_000.0,
+                "Mbits/sec" => 1_000_000.0,
+                "Kbits/sec" => 1_000.0,
+                _ => 1.0,
+            };
+            Some(value * scale)
+        })
+        .unwrap_or(0.0)
+}
+
+// Drive virtio-block throughput with fio against `control.fio_ops`, using
+// `control.queue_num` virtio-block queues. When a load sweep is in
+// progress, `control.offered_load` caps fio's `--rate` (bytes/sec) so
+// each sweep step measures achieved throughput at a different offered
+// rate instead of re-running the same uncapped test.
+pub fn performance_block_io(control: &PerformanceTestControl) -> f64 {
+    let rw = control.fio_ops.map(|o| o.to_string()).unwrap_or_default();
+    let rate = match control.offered_load {
+        Some(rate) => format!("--rate={rate}"),
+        None => String::new(),
+    };
+
+    let cmd = format!(
+        "fio --name=perf --rw={} --runtime={} --time_based --numjobs={} --iodepth={} --output-format=json {}",
+        rw,
+        control.test_time,
+        control.queue_num.unwrap_or(1),
+        control.queue_size.unwrap_or(32),
+        rate
+    );
+    let output = run_tracked(&cmd);
+    parse_fio_bps(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_fio_bps(output: &str) -> f64 {
+    // fio --output-format=json reports bandwidth in KiB/s as "bw": <num>
+    // under the relevant read/write block.
+    output
+        .lines()
+        .find_map(|l| l.trim().strip_prefix("\"bw\":"))
+        .and_then(|v| v.trim_end_matches(',').trim().parse::<f64>().ok())
+        .map(|kib_per_sec| kib_per_sec * 1024.0 * 8.0)
+        .unwrap_or(0.0)
+}